@@ -1,11 +1,57 @@
-use std::cmp::min;
+use std::cmp::{min, Ordering};
+use std::collections::{BinaryHeap, HashMap, HashSet};
 
 #[derive(Debug, Clone)]
 struct Order {
     id: u32,
     side: OrderSide,
-    price: f64,
+    order_type: OrderType,
     quantity: u32,
+    // Monotonically increasing arrival order, used to break ties between
+    // orders resting at the same price (price-time / FIFO priority).
+    sequence: u64,
+    tif: TimeInForce,
+    // All-or-none: if false, this order must fill in full or not trade at
+    // all, even outside of `TimeInForce::FillOrKill` (e.g. a good-till-cancel
+    // order that should never be left partially filled). Such an order is
+    // killed rather than rested when it can't fill in full.
+    partially_fillable: bool,
+}
+
+impl Order {
+    // Resolves the price this order is willing to match at, given the
+    // current NBBO. A limit order uses its own price; a market order takes
+    // whatever price is available up to the opposite touch (ask for a buy,
+    // bid for a sell). A stop order has no matching price until it triggers,
+    // so it returns `None` and does not participate in matching.
+    fn effective_price(&self, nbbo: &NBBO) -> Option<f64> {
+        match self.order_type {
+            OrderType::Limit { price } => Some(price),
+            OrderType::Market => Some(match self.side {
+                OrderSide::Buy => nbbo.ask,
+                OrderSide::Sell => nbbo.bid,
+            }),
+            OrderType::Stop { .. } => None,
+        }
+    }
+
+    // The limit price of a resting order. Only `Limit` orders ever rest on
+    // the book (market orders fill-or-drop, stop orders wait off-book until
+    // triggered), so this panics if that invariant is violated.
+    fn limit_price(&self) -> f64 {
+        match self.order_type {
+            OrderType::Limit { price } => price,
+            _ => panic!("order ID {}: only limit orders may rest on the book", self.id),
+        }
+    }
+
+    // Whether this order must fill completely or not trade at all. True for
+    // `TimeInForce::FillOrKill` (a one-shot all-or-nothing attempt) and for
+    // any order explicitly marked `partially_fillable: false` (an all-or-none
+    // order, whatever its time-in-force).
+    fn requires_full_fill(&self) -> bool {
+        self.tif == TimeInForce::FillOrKill || !self.partially_fillable
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -14,7 +60,26 @@ enum OrderSide {
     Sell,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OrderType {
+    Limit { price: f64 },
+    Market,
+    Stop { trigger_price: f64 },
+}
+
+// Governs how long an order may rest before its unfilled quantity is dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum TimeInForce {
+    // Fill what you can immediately, drop the rest.
+    ImmediateOrCancel,
+    // Fill the full quantity immediately, or do nothing at all.
+    FillOrKill,
+    // Fill what you can immediately, rest the remainder on the book.
+    #[default]
+    GoodTillCancel,
+}
+
+#[derive(Debug, PartialEq)]
 struct Trade {
     buy_order_id: u32,
     sell_order_id: u32,
@@ -22,38 +87,208 @@ struct Trade {
     clearing_price: f64,
 }
 
+// An order's terminal disposition for this matching pass.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FillStatus {
+    Filled,
+    PartiallyFilled,
+    Unfilled,
+    // An all-or-none order (`FillOrKill`, or `partially_fillable: false`)
+    // that couldn't fill in full, so none of its trades were committed.
+    Killed,
+}
+
+// Per-order settlement detail alongside the `Trade`s a matching pass
+// produced: how much of the order filled, how much is left, and its
+// terminal status.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct OrderFillReport {
+    order_id: u32,
+    filled_qty: u32,
+    remaining_qty: u32,
+    status: FillStatus,
+}
+
 struct NBBO {
     bid: f64,
     ask: f64,
 }
 
+// Market microstructure constraints every order must satisfy before it can
+// be matched: prices must land on a tick, and quantities must be a whole
+// number of lots no smaller than the minimum order size.
+#[derive(Debug, Clone, Copy)]
+struct MarketParams {
+    tick_size: f64,
+    lot_size: u32,
+    min_size: u32,
+}
+
+impl Default for MarketParams {
+    fn default() -> Self {
+        MarketParams { tick_size: 0.01, lot_size: 1, min_size: 1 }
+    }
+}
+
+// Why an order was rejected before it could be matched. Carries the
+// offending order's ID so callers can trace the failure back to its source.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OrderError {
+    InvalidSide { order_id: u32 },
+    PriceNotOnTick { order_id: u32 },
+    BelowMinSize { order_id: u32 },
+    InvalidLotSize { order_id: u32 },
+    PriceOutsideNbbo { order_id: u32 },
+}
+
+// Validates an order against the book's market microstructure constraints,
+// independent of which side of the book it's headed to (side-vs-list checks
+// are the caller's responsibility, since only `AuctionEngine`'s batches have
+// a notion of "the wrong list"). A `Market` order has no price of its own, so
+// it skips the tick/NBBO price checks; a `Stop` order's trigger price is
+// checked against the tick but not the NBBO, since it may legitimately sit
+// outside the current band until the market moves to it.
+fn validate_order(order: &Order, nbbo: &NBBO, params: &MarketParams) -> Result<(), OrderError> {
+    if order.quantity < params.min_size {
+        return Err(OrderError::BelowMinSize { order_id: order.id });
+    }
+    if !order.quantity.is_multiple_of(params.lot_size) {
+        return Err(OrderError::InvalidLotSize { order_id: order.id });
+    }
+
+    let tick_checked_price = match order.order_type {
+        OrderType::Limit { price } => Some(price),
+        OrderType::Stop { trigger_price } => Some(trigger_price),
+        OrderType::Market => None,
+    };
+    if let Some(price) = tick_checked_price {
+        let ticks = price / params.tick_size;
+        if (ticks - ticks.round()).abs() > 1e-6 {
+            return Err(OrderError::PriceNotOnTick { order_id: order.id });
+        }
+    }
+
+    if let OrderType::Limit { price } = order.order_type {
+        if price < nbbo.bid || price > nbbo.ask {
+            return Err(OrderError::PriceOutsideNbbo { order_id: order.id });
+        }
+    }
+
+    Ok(())
+}
+
+// Governs how the executable volume at the clearing price is divided among
+// orders on a side that can't all be filled in full.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum AllocationPolicy {
+    // Fill eligible orders priced better than the marginal (binding) price
+    // level in full, then split what's left across the marginal level's
+    // orders proportional to their size. Orders above the marginal level
+    // are not rationed.
+    ProRata,
+    // Fill the earliest-resting orders in full before moving to the next,
+    // i.e. the order in which `run_combinatorial_auction` has always walked
+    // the book.
+    #[default]
+    PriceTime,
+}
+
 struct AuctionEngine {
     nbbo: NBBO,
+    params: MarketParams,
+    allocation_policy: AllocationPolicy,
+}
+
+// The result of the clearing-price search: the single uniform price and the
+// executable volume it unlocks.
+struct ClearingPrice {
+    price: f64,
+    volume: u32,
 }
 
 impl AuctionEngine {
     fn new(nbbo: NBBO) -> Self {
-        AuctionEngine { nbbo }
+        Self::with_params(nbbo, MarketParams::default())
     }
 
-    fn run_greedy_auction(&self, buy_orders: &[Order], sell_orders: &[Order]) -> Vec<Trade> {
-        let mut trades = Vec::new();
-        let mut buy_orders = buy_orders.to_vec();
-        let mut sell_orders = sell_orders.to_vec();
+    fn with_params(nbbo: NBBO, params: MarketParams) -> Self {
+        Self::with_allocation_policy(nbbo, params, AllocationPolicy::default())
+    }
+
+    fn with_allocation_policy(nbbo: NBBO, params: MarketParams, allocation_policy: AllocationPolicy) -> Self {
+        AuctionEngine { nbbo, params, allocation_policy }
+    }
+
+    fn run_greedy_auction(
+        &self,
+        buy_orders: &[Order],
+        sell_orders: &[Order],
+    ) -> Result<(Vec<Trade>, Vec<OrderFillReport>), OrderError> {
+        let original_buy_orders = buy_orders.to_vec();
+        let original_sell_orders = sell_orders.to_vec();
 
-        for order in &buy_orders {
+        for order in buy_orders {
             if order.side != OrderSide::Buy {
-                panic!("Invalid buy order ID {}: expected Buy, found {:?}", order.id, order.side);
+                return Err(OrderError::InvalidSide { order_id: order.id });
             }
+            validate_order(order, &self.nbbo, &self.params)?;
         }
-        for order in &sell_orders {
+        for order in sell_orders {
             if order.side != OrderSide::Sell {
-                panic!("Invalid sell order ID {}: expected Sell, found {:?}", order.id, order.side);
+                return Err(OrderError::InvalidSide { order_id: order.id });
             }
+            validate_order(order, &self.nbbo, &self.params)?;
         }
 
-        buy_orders.sort_by(|a, b| b.price.partial_cmp(&a.price).unwrap());
-        sell_orders.sort_by(|a, b| a.price.partial_cmp(&b.price).unwrap());
+        // A one-shot batch has no notion of a prior trade, so stop orders
+        // (which trigger off the last trade price) never participate here;
+        // they only make sense against `OrderBook`'s persistent state.
+        let mut buy_orders: Vec<Order> = buy_orders
+            .iter()
+            .filter(|order| order.effective_price(&self.nbbo).is_some())
+            .cloned()
+            .collect();
+        let mut sell_orders: Vec<Order> = sell_orders
+            .iter()
+            .filter(|order| order.effective_price(&self.nbbo).is_some())
+            .cloned()
+            .collect();
+
+        buy_orders.sort_by(|a, b| {
+            b.effective_price(&self.nbbo)
+                .unwrap()
+                .partial_cmp(&a.effective_price(&self.nbbo).unwrap())
+                .unwrap()
+                .then_with(|| a.sequence.cmp(&b.sequence))
+        });
+        sell_orders.sort_by(|a, b| {
+            a.effective_price(&self.nbbo)
+                .unwrap()
+                .partial_cmp(&b.effective_price(&self.nbbo).unwrap())
+                .unwrap()
+                .then_with(|| a.sequence.cmp(&b.sequence))
+        });
+
+        let (trades, killed_buy_ids, killed_sell_ids) = self.commit_after_excluding_unfillable(
+            buy_orders,
+            sell_orders,
+            &original_buy_orders,
+            &original_sell_orders,
+            Self::match_greedy_orders,
+        );
+
+        let reports = build_fill_reports(&trades, &original_buy_orders, &original_sell_orders, &killed_buy_ids, &killed_sell_ids);
+        Ok((trades, reports))
+    }
+
+    // Nested-loop price-time match over an already filtered, already sorted
+    // set of orders. Pure function of its inputs: callers re-run it on a
+    // trimmed order set to commit a fill once all-or-none orders that can't
+    // fill in full have been excluded (see `run_greedy_auction`).
+    fn match_greedy_orders(&self, buy_orders: &[Order], sell_orders: &[Order]) -> Vec<Trade> {
+        let mut buy_orders = buy_orders.to_vec();
+        let mut sell_orders = sell_orders.to_vec();
+        let mut trades = Vec::new();
 
         for buy_idx in 0..buy_orders.len() {
             for sell_idx in 0..sell_orders.len() {
@@ -61,8 +296,8 @@ impl AuctionEngine {
                     continue;
                 }
 
-                let buy_price = buy_orders[buy_idx].price;
-                let sell_price = sell_orders[sell_idx].price;
+                let buy_price = buy_orders[buy_idx].effective_price(&self.nbbo).unwrap();
+                let sell_price = sell_orders[sell_idx].effective_price(&self.nbbo).unwrap();
 
                 if buy_price >= sell_price
                     && buy_price <= self.nbbo.ask
@@ -87,75 +322,742 @@ impl AuctionEngine {
         trades
     }
 
-    // New combinatorial version: multi-unit double auction with uniform clearing price
-    fn run_combinatorial_auction(&self, buy_orders: &[Order], sell_orders: &[Order]) -> Vec<Trade> {
-        let mut trades = Vec::new();
-        let mut buy_orders = buy_orders.to_vec();
-        let mut sell_orders = sell_orders.to_vec();
+    // Combinatorial version: multi-unit double auction with a volume-maximizing
+    // uniform clearing price (the standard single-price call-auction algorithm
+    // used by batch exchanges), rather than a price derived from whichever pair
+    // happened to match last.
+    fn run_combinatorial_auction(
+        &self,
+        buy_orders: &[Order],
+        sell_orders: &[Order],
+    ) -> Result<(Vec<Trade>, Vec<OrderFillReport>), OrderError> {
+        let original_buy_orders = buy_orders.to_vec();
+        let original_sell_orders = sell_orders.to_vec();
 
-        for order in &buy_orders {
+        for order in buy_orders {
             if order.side != OrderSide::Buy {
-                panic!("Invalid buy order ID {}: expected Buy, found {:?}", order.id, order.side);
+                return Err(OrderError::InvalidSide { order_id: order.id });
             }
+            validate_order(order, &self.nbbo, &self.params)?;
         }
-        for order in &sell_orders {
+        for order in sell_orders {
             if order.side != OrderSide::Sell {
-                panic!("Invalid sell order ID {}: expected Sell, found {:?}", order.id, order.side);
+                return Err(OrderError::InvalidSide { order_id: order.id });
             }
+            validate_order(order, &self.nbbo, &self.params)?;
         }
 
-        buy_orders.sort_by(|a, b| b.price.partial_cmp(&a.price).unwrap());
-        sell_orders.sort_by(|a, b| a.price.partial_cmp(&b.price).unwrap());
+        // See `run_greedy_auction`: stop orders have no last-trade context in
+        // a stateless batch, so they don't participate.
+        let mut buy_orders: Vec<Order> = buy_orders
+            .iter()
+            .filter(|order| order.effective_price(&self.nbbo).is_some())
+            .cloned()
+            .collect();
+        let mut sell_orders: Vec<Order> = sell_orders
+            .iter()
+            .filter(|order| order.effective_price(&self.nbbo).is_some())
+            .cloned()
+            .collect();
 
-        // First pass: find matches, total volume, and marginal prices (no trades yet)
-        let mut matches: Vec<(u32, u32, u32)> = Vec::new(); // (buy_id, sell_id, quantity)
-        let mut marginal_buy = 0.0;
-        let mut marginal_sell = 0.0;
+        buy_orders.sort_by(|a, b| {
+            b.effective_price(&self.nbbo)
+                .unwrap()
+                .partial_cmp(&a.effective_price(&self.nbbo).unwrap())
+                .unwrap()
+                .then_with(|| a.sequence.cmp(&b.sequence))
+        });
+        sell_orders.sort_by(|a, b| {
+            a.effective_price(&self.nbbo)
+                .unwrap()
+                .partial_cmp(&b.effective_price(&self.nbbo).unwrap())
+                .unwrap()
+                .then_with(|| a.sequence.cmp(&b.sequence))
+        });
 
-        for buy_idx in 0..buy_orders.len() {
-            for sell_idx in 0..sell_orders.len() {
-                if buy_orders[buy_idx].quantity == 0 || sell_orders[sell_idx].quantity == 0 {
+        let (trades, killed_buy_ids, killed_sell_ids) = self.commit_after_excluding_unfillable(
+            buy_orders,
+            sell_orders,
+            &original_buy_orders,
+            &original_sell_orders,
+            Self::match_combinatorial_orders,
+        );
+
+        let reports = build_fill_reports(&trades, &original_buy_orders, &original_sell_orders, &killed_buy_ids, &killed_sell_ids);
+        Ok((trades, reports))
+    }
+
+    // Dry-runs `matcher` against the active order set to find all-or-none
+    // orders that can't fill in full, then excludes the highest-priority one
+    // and repeats — rather than excluding every order killed in a single
+    // snapshot at once. A one-shot exclusion can wrongly kill a lower-priority
+    // all-or-none order purely because a higher-priority all-or-none order
+    // (which is itself about to be excluded) consumed the liquidity it would
+    // have needed; iterating one exclusion at a time lets that order be
+    // re-evaluated against the liquidity actually left once the orders ahead
+    // of it are gone.
+    fn commit_after_excluding_unfillable(
+        &self,
+        mut buy_orders: Vec<Order>,
+        mut sell_orders: Vec<Order>,
+        original_buy_orders: &[Order],
+        original_sell_orders: &[Order],
+        matcher: impl Fn(&Self, &[Order], &[Order]) -> Vec<Trade>,
+    ) -> (Vec<Trade>, HashSet<u32>, HashSet<u32>) {
+        let mut excluded_buy_ids: HashSet<u32> = HashSet::new();
+        let mut excluded_sell_ids: HashSet<u32> = HashSet::new();
+
+        loop {
+            let trades = matcher(self, &buy_orders, &sell_orders);
+            let (killed_buy_ids, killed_sell_ids) =
+                compute_killed_ids(&trades, original_buy_orders, original_sell_orders);
+
+            let newly_killed_buy = buy_orders.iter().find(|order| killed_buy_ids.contains(&order.id));
+            if let Some(order) = newly_killed_buy {
+                let id = order.id;
+                excluded_buy_ids.insert(id);
+                buy_orders.retain(|order| order.id != id);
+                continue;
+            }
+
+            let newly_killed_sell = sell_orders.iter().find(|order| killed_sell_ids.contains(&order.id));
+            if let Some(order) = newly_killed_sell {
+                let id = order.id;
+                excluded_sell_ids.insert(id);
+                sell_orders.retain(|order| order.id != id);
+                continue;
+            }
+
+            return (trades, excluded_buy_ids, excluded_sell_ids);
+        }
+    }
+
+    // Single-price clearing over an already filtered, already sorted set of
+    // orders. Pure function of its inputs: callers re-run it on a trimmed
+    // order set to commit a fill once all-or-none orders that can't fill in
+    // full have been excluded (see `run_combinatorial_auction`).
+    fn match_combinatorial_orders(&self, buy_orders: &[Order], sell_orders: &[Order]) -> Vec<Trade> {
+        let Some(clearing) = self.find_clearing_price(buy_orders, sell_orders) else {
+            return Vec::new();
+        };
+
+        // Eligible orders (buys at or above, sells at or below the clearing
+        // price) are the ones that participate in the fill; their sets are
+        // still in price-time order since `buy_orders`/`sell_orders` were.
+        let eligible_buys: Vec<Order> = buy_orders
+            .iter()
+            .filter(|buy| buy.effective_price(&self.nbbo).unwrap() >= clearing.price)
+            .cloned()
+            .collect();
+        let eligible_sells: Vec<Order> = sell_orders
+            .iter()
+            .filter(|sell| sell.effective_price(&self.nbbo).unwrap() <= clearing.price)
+            .cloned()
+            .collect();
+
+        // Whichever side has more eligible quantity than the executable
+        // volume can't all be filled in full; ration it per the configured
+        // allocation policy. The other side's eligible quantity is already
+        // exactly the executable volume, so it needs no rationing.
+        let (alloc_buys, alloc_sells) = match self.allocation_policy {
+            AllocationPolicy::PriceTime => (eligible_buys, eligible_sells),
+            AllocationPolicy::ProRata => (
+                self.apply_pro_rata(&eligible_buys, clearing.volume),
+                self.apply_pro_rata(&eligible_sells, clearing.volume),
+            ),
+        };
+
+        // Walk both (already-rationed) sides in price-time order, pairing up
+        // trades until the executable volume is exhausted.
+        let mut trades = Vec::new();
+        let mut remaining_volume = clearing.volume;
+        let mut sell_idx = 0;
+        let mut alloc_sells = alloc_sells;
+
+        for mut buy in alloc_buys {
+            if remaining_volume == 0 {
+                break;
+            }
+
+            while remaining_volume > 0 && buy.quantity > 0 && sell_idx < alloc_sells.len() {
+                if alloc_sells[sell_idx].quantity == 0 {
+                    sell_idx += 1;
                     continue;
                 }
 
-                let buy_price = buy_orders[buy_idx].price;
-                let sell_price = sell_orders[sell_idx].price;
+                let match_quantity = min(buy.quantity, alloc_sells[sell_idx].quantity)
+                    .min(remaining_volume);
 
-                if buy_price >= sell_price
-                    && buy_price <= self.nbbo.ask
-                    && sell_price >= self.nbbo.bid
-                {
-                    let match_quantity = min(buy_orders[buy_idx].quantity, sell_orders[sell_idx].quantity);
+                trades.push(Trade {
+                    buy_order_id: buy.id,
+                    sell_order_id: alloc_sells[sell_idx].id,
+                    quantity: match_quantity,
+                    clearing_price: clearing.price,
+                });
 
-                    matches.push((buy_orders[buy_idx].id, sell_orders[sell_idx].id, match_quantity));
+                buy.quantity -= match_quantity;
+                alloc_sells[sell_idx].quantity -= match_quantity;
+                remaining_volume -= match_quantity;
 
-                    marginal_buy = buy_price;
-                    marginal_sell = sell_price;
+                if alloc_sells[sell_idx].quantity == 0 {
+                    sell_idx += 1;
+                }
+            }
+        }
 
-                    buy_orders[buy_idx].quantity -= match_quantity;
-                    sell_orders[sell_idx].quantity -= match_quantity;
+        trades
+    }
+
+    // When a side's eligible quantity exceeds the executable volume, its
+    // orders can't all be filled in full. Price priority still applies
+    // across price levels, though: orders strictly better than the binding
+    // (marginal) price fill in full first, walking `orders` in its existing
+    // price-time order. Only the level where cumulative demand finally
+    // exceeds the remaining volume — the marginal price — gets rationed;
+    // every level beyond it gets nothing. `orders` must already be sorted in
+    // price-time order (ascending or descending, as the side requires).
+    fn apply_pro_rata(&self, orders: &[Order], volume: u32) -> Vec<Order> {
+        let mut allocated = Vec::with_capacity(orders.len());
+        let mut remaining_volume = volume;
+        let mut idx = 0;
+
+        while idx < orders.len() && remaining_volume > 0 {
+            let level_price = orders[idx].effective_price(&self.nbbo).unwrap();
+            let mut end = idx + 1;
+            while end < orders.len()
+                && (orders[end].effective_price(&self.nbbo).unwrap() - level_price).abs() < f64::EPSILON
+            {
+                end += 1;
+            }
+
+            let level = &orders[idx..end];
+            let level_qty: u32 = level.iter().map(|order| order.quantity).sum();
+
+            if level_qty <= remaining_volume {
+                // Strictly better than the marginal price (or a tied level
+                // that still fits in what's left): everyone here fills in full.
+                allocated.extend(level.iter().cloned());
+                remaining_volume -= level_qty;
+            } else {
+                // The marginal price level: demand here exceeds what's left,
+                // so ration this group pro-rata and stop.
+                allocated.extend(self.ration_level(level, remaining_volume));
+                remaining_volume = 0;
+            }
+
+            idx = end;
+        }
+
+        allocated
+    }
+
+    // Rations a single (marginal) price level's worth of oversubscribed
+    // orders: each gets a fill proportional to its size
+    // (`floor(volume * qty / total_qty)`, rounded down to a whole number of
+    // lots), then the leftover lots are handed out one at a time to the
+    // orders with the largest fractional remainder, breaking ties by
+    // earliest sequence.
+    fn ration_level(&self, orders: &[Order], volume: u32) -> Vec<Order> {
+        let total_qty: u32 = orders.iter().map(|order| order.quantity).sum();
+        let lot_size = self.params.lot_size;
+        let mut allocated: Vec<(Order, f64)> = orders
+            .iter()
+            .map(|order| {
+                let raw = volume as f64 * order.quantity as f64 / total_qty as f64;
+                let lots = (raw / lot_size as f64).floor() as u32;
+                let base = (lots * lot_size).min(order.quantity);
+                (Order { quantity: base, ..order.clone() }, raw - base as f64)
+            })
+            .collect();
+
+        let mut leftover = volume - allocated.iter().map(|(order, _)| order.quantity).sum::<u32>();
+        let mut by_remainder: Vec<usize> = (0..allocated.len()).collect();
+        by_remainder.sort_by(|&a, &b| {
+            allocated[b]
+                .1
+                .partial_cmp(&allocated[a].1)
+                .unwrap()
+                .then_with(|| allocated[a].0.sequence.cmp(&allocated[b].0.sequence))
+        });
+
+        let mut progress = true;
+        while leftover > 0 && progress {
+            progress = false;
+            for &idx in &by_remainder {
+                if leftover == 0 {
+                    break;
+                }
+                let room = orders[idx].quantity - allocated[idx].0.quantity;
+                let grant = lot_size.min(room).min(leftover);
+                if grant > 0 {
+                    allocated[idx].0.quantity += grant;
+                    leftover -= grant;
+                    progress = true;
                 }
             }
         }
 
-        // Compute uniform clearing price if there were matches
-        if matches.is_empty() {
-            return trades; // No trades
+        allocated.into_iter().map(|(order, _)| order).collect()
+    }
+
+    // Finds the single uniform price that maximizes executable volume, the core
+    // of the call-auction clearing algorithm: candidate prices are the union of
+    // all limit prices (clamped to the NBBO band), ties are broken first by
+    // minimizing the demand/supply imbalance, then by the midpoint of the tied
+    // price range.
+    fn find_clearing_price(&self, buy_orders: &[Order], sell_orders: &[Order]) -> Option<ClearingPrice> {
+        let mut candidates: Vec<f64> = buy_orders
+            .iter()
+            .chain(sell_orders.iter())
+            .map(|order| order.effective_price(&self.nbbo).unwrap().clamp(self.nbbo.bid, self.nbbo.ask))
+            .collect();
+        candidates.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        candidates.dedup_by(|a, b| (*a - *b).abs() < f64::EPSILON);
+
+        let mut best_volume = 0u32;
+        let mut best_imbalance = u32::MAX;
+        let mut tied_prices: Vec<f64> = Vec::new();
+
+        for &price in &candidates {
+            let demand: u32 = buy_orders
+                .iter()
+                .filter(|order| order.effective_price(&self.nbbo).unwrap() >= price)
+                .map(|order| order.quantity)
+                .sum();
+            let supply: u32 = sell_orders
+                .iter()
+                .filter(|order| order.effective_price(&self.nbbo).unwrap() <= price)
+                .map(|order| order.quantity)
+                .sum();
+            let volume = demand.min(supply);
+            let imbalance = demand.abs_diff(supply);
+
+            if volume > best_volume || (volume == best_volume && volume > 0 && imbalance < best_imbalance) {
+                best_volume = volume;
+                best_imbalance = imbalance;
+                tied_prices = vec![price];
+            } else if volume == best_volume && volume > 0 && imbalance == best_imbalance {
+                tied_prices.push(price);
+            }
+        }
+
+        if best_volume == 0 {
+            return None;
+        }
+
+        let lo = tied_prices.iter().cloned().fold(f64::INFINITY, f64::min);
+        let hi = tied_prices.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+
+        Some(ClearingPrice {
+            price: (lo + hi) / 2.0,
+            volume: best_volume,
+        })
+    }
+}
+
+// Determines which all-or-none orders (`FillOrKill`, or `partially_fillable:
+// false`) wouldn't fill in full against a dry-run `trades` pass, so callers
+// can exclude them before committing a final matching pass (a to-be-killed
+// order's simulated matches must never consume liquidity a different,
+// fillable order in the same batch needed) and report them as
+// `FillStatus::Killed`.
+fn compute_killed_ids(
+    trades: &[Trade],
+    buy_orders: &[Order],
+    sell_orders: &[Order],
+) -> (HashSet<u32>, HashSet<u32>) {
+    let mut filled_buy_qty: HashMap<u32, u32> = HashMap::new();
+    let mut filled_sell_qty: HashMap<u32, u32> = HashMap::new();
+    for trade in trades {
+        *filled_buy_qty.entry(trade.buy_order_id).or_insert(0) += trade.quantity;
+        *filled_sell_qty.entry(trade.sell_order_id).or_insert(0) += trade.quantity;
+    }
+
+    let killed_buy_ids = buy_orders
+        .iter()
+        .filter(|order| order.requires_full_fill())
+        .filter(|order| filled_buy_qty.get(&order.id).copied().unwrap_or(0) < order.quantity)
+        .map(|order| order.id)
+        .collect();
+    let killed_sell_ids = sell_orders
+        .iter()
+        .filter(|order| order.requires_full_fill())
+        .filter(|order| filled_sell_qty.get(&order.id).copied().unwrap_or(0) < order.quantity)
+        .map(|order| order.id)
+        .collect();
+
+    (killed_buy_ids, killed_sell_ids)
+}
+
+// Builds the per-order fill report for a batch auction pass: how much of
+// each original order filled, how much is left, and its terminal status.
+fn build_fill_reports(
+    trades: &[Trade],
+    buy_orders: &[Order],
+    sell_orders: &[Order],
+    killed_buy_ids: &HashSet<u32>,
+    killed_sell_ids: &HashSet<u32>,
+) -> Vec<OrderFillReport> {
+    let mut filled_buy_qty: HashMap<u32, u32> = HashMap::new();
+    let mut filled_sell_qty: HashMap<u32, u32> = HashMap::new();
+    for trade in trades {
+        *filled_buy_qty.entry(trade.buy_order_id).or_insert(0) += trade.quantity;
+        *filled_sell_qty.entry(trade.sell_order_id).or_insert(0) += trade.quantity;
+    }
+
+    let report = |order: &Order, filled_qty: &HashMap<u32, u32>, killed_ids: &HashSet<u32>| {
+        let filled = filled_qty.get(&order.id).copied().unwrap_or(0);
+        let status = if killed_ids.contains(&order.id) {
+            FillStatus::Killed
+        } else if filled == 0 {
+            FillStatus::Unfilled
+        } else if filled >= order.quantity {
+            FillStatus::Filled
+        } else {
+            FillStatus::PartiallyFilled
+        };
+        OrderFillReport {
+            order_id: order.id,
+            filled_qty: filled,
+            remaining_qty: order.quantity - filled,
+            status,
+        }
+    };
+
+    buy_orders
+        .iter()
+        .map(|order| report(order, &filled_buy_qty, killed_buy_ids))
+        .chain(sell_orders.iter().map(|order| report(order, &filled_sell_qty, killed_sell_ids)))
+        .collect()
+}
+
+// Wraps a resting bid so a max-heap pops the highest price first.
+#[derive(Debug, Clone)]
+struct BidOrder(Order);
+
+impl PartialEq for BidOrder {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.limit_price() == other.0.limit_price()
+    }
+}
+
+impl Eq for BidOrder {}
+
+impl PartialOrd for BidOrder {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BidOrder {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Higher price first; among equal prices, earlier sequence (FIFO) first.
+        self.0
+            .limit_price()
+            .partial_cmp(&other.0.limit_price())
+            .unwrap()
+            .then_with(|| other.0.sequence.cmp(&self.0.sequence))
+    }
+}
+
+// Wraps a resting ask so a max-heap pops the lowest price first.
+#[derive(Debug, Clone)]
+struct AskOrder(Order);
+
+impl PartialEq for AskOrder {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.limit_price() == other.0.limit_price()
+    }
+}
+
+impl Eq for AskOrder {}
+
+impl PartialOrd for AskOrder {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for AskOrder {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Lower price first; among equal prices, earlier sequence (FIFO) first.
+        other
+            .0
+            .limit_price()
+            .partial_cmp(&self.0.limit_price())
+            .unwrap()
+            .then_with(|| other.0.sequence.cmp(&self.0.sequence))
+    }
+}
+
+// A continuous limit order book: resting bids and asks persist across calls
+// instead of being discarded like `AuctionEngine`'s one-shot batches. Incoming
+// orders match against the opposite side immediately; any unfilled remainder
+// rests on the book (Limit orders with `GoodTillCancel`), is dropped
+// (`ImmediateOrCancel`, or a Market order that can't rest at all), or, for
+// `FillOrKill`, prevents any of the order's trades from happening at all
+// unless it can be filled in full.
+//
+// Stop orders are held off-book in `pending_stops` until the last trade
+// price crosses their trigger, at which point they're resubmitted as market
+// orders.
+//
+// Cancellation and amendment are lazy: rather than searching the heaps,
+// `remaining` is the source of truth for how much of an order is still live.
+// A resting order popped off a heap with no entry (or a zero entry) in
+// `remaining` is stale and is simply dropped instead of matched.
+struct OrderBook {
+    nbbo: NBBO,
+    params: MarketParams,
+    bids: BinaryHeap<BidOrder>,
+    asks: BinaryHeap<AskOrder>,
+    remaining: HashMap<u32, u32>,
+    pending_stops: Vec<Order>,
+    last_trade_price: Option<f64>,
+}
+
+impl OrderBook {
+    fn new(nbbo: NBBO) -> Self {
+        Self::with_params(nbbo, MarketParams::default())
+    }
+
+    fn with_params(nbbo: NBBO, params: MarketParams) -> Self {
+        OrderBook {
+            nbbo,
+            params,
+            bids: BinaryHeap::new(),
+            asks: BinaryHeap::new(),
+            remaining: HashMap::new(),
+            pending_stops: Vec::new(),
+            last_trade_price: None,
+        }
+    }
+
+    // Matches `order` against the opposite side of the book, honoring its
+    // order type and time-in-force, producing a `Trade` for each filled
+    // portion.
+    fn submit(&mut self, order: Order) -> Result<(Vec<Trade>, OrderFillReport), OrderError> {
+        validate_order(&order, &self.nbbo, &self.params)?;
+        let original_quantity = order.quantity;
+
+        if let OrderType::Stop { trigger_price } = order.order_type {
+            if !self.stop_triggered(&order.side, trigger_price) {
+                let order_id = order.id;
+                self.pending_stops.push(order);
+                return Ok((
+                    Vec::new(),
+                    OrderFillReport { order_id, filled_qty: 0, remaining_qty: original_quantity, status: FillStatus::Unfilled },
+                ));
+            }
+            return self.submit(Order { order_type: OrderType::Market, ..order });
+        }
+
+        // All-or-none (`FillOrKill`, or `partially_fillable: false`) orders
+        // are checked for full fillability up front, before any of their
+        // trades are committed, rather than rolled back after the fact.
+        if order.requires_full_fill() && !self.can_fill_in_full(&order) {
+            return Ok((
+                Vec::new(),
+                OrderFillReport { order_id: order.id, filled_qty: 0, remaining_qty: original_quantity, status: FillStatus::Killed },
+            ));
+        }
+
+        let mut order = order;
+        let mut trades = self.match_against_book(&mut order);
+
+        let order_id = order.id;
+        let remaining_qty = order.quantity;
+        let filled_qty = original_quantity - remaining_qty;
+
+        // Only a resting `Limit` order under `GoodTillCancel` stays on the
+        // book; a Market order has no price to rest at, `ImmediateOrCancel`
+        // drops the remainder, and an all-or-none order is guaranteed fully
+        // filled by the check above.
+        if remaining_qty > 0
+            && order.tif == TimeInForce::GoodTillCancel
+            && matches!(order.order_type, OrderType::Limit { .. })
+        {
+            self.remaining.insert(order.id, remaining_qty);
+            match order.side {
+                OrderSide::Buy => self.bids.push(BidOrder(order)),
+                OrderSide::Sell => self.asks.push(AskOrder(order)),
+            }
+        }
+
+        let status = if filled_qty == 0 {
+            FillStatus::Unfilled
+        } else if remaining_qty == 0 {
+            FillStatus::Filled
+        } else {
+            FillStatus::PartiallyFilled
+        };
+
+        if let Some(last_trade) = trades.last() {
+            self.last_trade_price = Some(last_trade.clearing_price);
+            let (stop_trades, _stop_reports) = self.activate_pending_stops()?;
+            trades.extend(stop_trades);
         }
-        let uniform_clearing_price = (marginal_buy + marginal_sell) / 2.0;
-
-        // Second pass: create trades at the uniform clearing price
-        for (buy_id, sell_id, quantity) in matches {
-            trades.push(Trade {
-                buy_order_id: buy_id,
-                sell_order_id: sell_id,
-                quantity,
-                clearing_price: uniform_clearing_price,
-            });
+
+        Ok((trades, OrderFillReport { order_id, filled_qty, remaining_qty, status }))
+    }
+
+    // The matching core shared by every order type/TIF combination: walks
+    // the opposite side of the book, consuming `order`'s quantity as long as
+    // prices cross within the NBBO band. Leaves any unfilled quantity on
+    // `order` for the caller to decide whether it rests or is dropped.
+    fn match_against_book(&mut self, order: &mut Order) -> Vec<Trade> {
+        let mut trades = Vec::new();
+        let Some(price) = order.effective_price(&self.nbbo) else {
+            return trades;
+        };
+
+        match order.side {
+            OrderSide::Buy => {
+                while order.quantity > 0 {
+                    let Some(AskOrder(ask)) = self.asks.peek().cloned() else { break };
+                    let ask_remaining = self.remaining.get(&ask.id).copied().unwrap_or(0);
+                    if ask_remaining == 0 {
+                        self.asks.pop();
+                        continue;
+                    }
+                    if price < ask.limit_price() || price > self.nbbo.ask || ask.limit_price() < self.nbbo.bid {
+                        break;
+                    }
+
+                    self.asks.pop();
+                    let match_quantity = min(order.quantity, ask_remaining);
+                    trades.push(Trade {
+                        buy_order_id: order.id,
+                        sell_order_id: ask.id,
+                        quantity: match_quantity,
+                        clearing_price: ask.limit_price(),
+                    });
+
+                    order.quantity -= match_quantity;
+                    let ask_left = ask_remaining - match_quantity;
+                    if ask_left > 0 {
+                        self.remaining.insert(ask.id, ask_left);
+                        self.asks.push(AskOrder(Order { quantity: ask_left, ..ask }));
+                    } else {
+                        self.remaining.remove(&ask.id);
+                    }
+                }
+            }
+            OrderSide::Sell => {
+                while order.quantity > 0 {
+                    let Some(BidOrder(bid)) = self.bids.peek().cloned() else { break };
+                    let bid_remaining = self.remaining.get(&bid.id).copied().unwrap_or(0);
+                    if bid_remaining == 0 {
+                        self.bids.pop();
+                        continue;
+                    }
+                    if price > bid.limit_price() || bid.limit_price() > self.nbbo.ask || price < self.nbbo.bid {
+                        break;
+                    }
+
+                    self.bids.pop();
+                    let match_quantity = min(order.quantity, bid_remaining);
+                    trades.push(Trade {
+                        buy_order_id: bid.id,
+                        sell_order_id: order.id,
+                        quantity: match_quantity,
+                        clearing_price: bid.limit_price(),
+                    });
+
+                    order.quantity -= match_quantity;
+                    let bid_left = bid_remaining - match_quantity;
+                    if bid_left > 0 {
+                        self.remaining.insert(bid.id, bid_left);
+                        self.bids.push(BidOrder(Order { quantity: bid_left, ..bid }));
+                    } else {
+                        self.remaining.remove(&bid.id);
+                    }
+                }
+            }
         }
 
         trades
     }
+
+    // Whether the resting liquidity opposite `order`, within the NBBO band,
+    // can cover its full quantity. Used to decide a `FillOrKill` order
+    // up front, before any of its trades are committed.
+    fn can_fill_in_full(&self, order: &Order) -> bool {
+        let Some(price) = order.effective_price(&self.nbbo) else { return false };
+
+        let available: u32 = match order.side {
+            OrderSide::Buy => self
+                .asks
+                .iter()
+                .map(|AskOrder(ask)| (ask, self.remaining.get(&ask.id).copied().unwrap_or(0)))
+                .filter(|(ask, qty)| {
+                    *qty > 0 && price >= ask.limit_price() && price <= self.nbbo.ask && ask.limit_price() >= self.nbbo.bid
+                })
+                .map(|(_, qty)| qty)
+                .sum(),
+            OrderSide::Sell => self
+                .bids
+                .iter()
+                .map(|BidOrder(bid)| (bid, self.remaining.get(&bid.id).copied().unwrap_or(0)))
+                .filter(|(bid, qty)| {
+                    *qty > 0 && price <= bid.limit_price() && bid.limit_price() <= self.nbbo.ask && price >= self.nbbo.bid
+                })
+                .map(|(_, qty)| qty)
+                .sum(),
+        };
+
+        available >= order.quantity
+    }
+
+    fn stop_triggered(&self, side: &OrderSide, trigger_price: f64) -> bool {
+        match self.last_trade_price {
+            Some(last) => match side {
+                OrderSide::Buy => last >= trigger_price,
+                OrderSide::Sell => last <= trigger_price,
+            },
+            None => false,
+        }
+    }
+
+    // Resubmits any pending stop order whose trigger the last trade just
+    // crossed, as a market order. Submitting can itself produce a new last
+    // trade price, so this can cascade through a chain of stops.
+    fn activate_pending_stops(&mut self) -> Result<(Vec<Trade>, Vec<OrderFillReport>), OrderError> {
+        let mut trades = Vec::new();
+        let mut reports = Vec::new();
+        for stop in std::mem::take(&mut self.pending_stops) {
+            let OrderType::Stop { trigger_price } = stop.order_type else {
+                unreachable!("pending_stops only ever holds Stop orders")
+            };
+            if self.stop_triggered(&stop.side, trigger_price) {
+                let (stop_trades, stop_report) = self.submit(Order { order_type: OrderType::Market, ..stop })?;
+                trades.extend(stop_trades);
+                reports.push(stop_report);
+            } else {
+                self.pending_stops.push(stop);
+            }
+        }
+        Ok((trades, reports))
+    }
+
+    // Pulls a resting order off the book. Returns `false` if the order is not
+    // currently resting (already filled, cancelled, or never submitted).
+    fn cancel(&mut self, order_id: u32) -> bool {
+        self.remaining.remove(&order_id).is_some()
+    }
+
+    // Reduces a resting order's remaining quantity to `new_qty`. Returns
+    // `false` if the order is not resting, or if `new_qty` is not a
+    // reduction.
+    fn amend(&mut self, order_id: u32, new_qty: u32) -> bool {
+        match self.remaining.get_mut(&order_id) {
+            Some(qty) if new_qty < *qty => {
+                *qty = new_qty;
+                true
+            }
+            _ => false,
+        }
+    }
 }
 
 fn main() {
@@ -171,14 +1073,20 @@ fn main() {
         Order {
             id: 1,
             side: OrderSide::Buy,
-            price: 100.00,
+            order_type: OrderType::Limit { price: 100.00 },
             quantity: 100,
+            sequence: 1,
+            tif: TimeInForce::GoodTillCancel,
+            partially_fillable: true,
         },
         Order {
             id: 2,
             side: OrderSide::Buy,
-            price: 99.75,
+            order_type: OrderType::Limit { price: 99.75 },
             quantity: 200,
+            sequence: 2,
+            tif: TimeInForce::GoodTillCancel,
+            partially_fillable: true,
         },
     ];
 
@@ -186,19 +1094,27 @@ fn main() {
         Order {
             id: 3,
             side: OrderSide::Sell,
-            price: 99.60,
+            order_type: OrderType::Limit { price: 99.60 },
             quantity: 150,
+            sequence: 3,
+            tif: TimeInForce::GoodTillCancel,
+            partially_fillable: true,
         },
         Order {
             id: 4,
             side: OrderSide::Sell,
-            price: 100.10,
+            order_type: OrderType::Limit { price: 100.10 },
             quantity: 100,
+            sequence: 4,
+            tif: TimeInForce::GoodTillCancel,
+            partially_fillable: true,
         },
     ];
 
     // Run greedy auction (per-pair prices)
-    let greedy_trades = engine.run_greedy_auction(&buy_orders, &sell_orders);
+    let (greedy_trades, _greedy_reports) = engine
+        .run_greedy_auction(&buy_orders, &sell_orders)
+        .expect("sample orders satisfy market constraints");
     println!("Greedy Matched Trades:");
     for trade in greedy_trades {
         println!(
@@ -211,7 +1127,9 @@ fn main() {
     }
 
     // Run combinatorial auction (uniform clearing price)
-    let combo_trades = engine.run_combinatorial_auction(&buy_orders, &sell_orders);
+    let (combo_trades, _combo_reports) = engine
+        .run_combinatorial_auction(&buy_orders, &sell_orders)
+        .expect("sample orders satisfy market constraints");
     println!("\nCombinatorial Matched Trades (Uniform Clearing):");
     for trade in combo_trades {
         println!(
@@ -222,6 +1140,136 @@ fn main() {
             trade.clearing_price
         );
     }
+
+    // Run a continuous order book: orders rest until matched, amended, or cancelled.
+    let mut book = OrderBook::new(NBBO { bid: 99.50, ask: 100.50 });
+    book.submit(Order { id: 5, side: OrderSide::Buy, order_type: OrderType::Limit { price: 99.80 }, quantity: 100, sequence: 5, tif: TimeInForce::GoodTillCancel, partially_fillable: true }).unwrap();
+    book.submit(Order { id: 7, side: OrderSide::Buy, order_type: OrderType::Limit { price: 99.65 }, quantity: 50, sequence: 7, tif: TimeInForce::GoodTillCancel, partially_fillable: true }).unwrap();
+    book.amend(7, 20);
+    book.cancel(7);
+    let (book_trades, _book_report) = book
+        .submit(Order { id: 6, side: OrderSide::Sell, order_type: OrderType::Limit { price: 99.70 }, quantity: 40, sequence: 6, tif: TimeInForce::GoodTillCancel, partially_fillable: true })
+        .unwrap();
+    println!("\nOrder Book Trades (Continuous Matching):");
+    for trade in book_trades {
+        println!(
+            "Trade: Buy Order {} <> Sell Order {}, Quantity: {}, Clearing Price: ${:.2}",
+            trade.buy_order_id,
+            trade.sell_order_id,
+            trade.quantity,
+            trade.clearing_price
+        );
+    }
+
+    // A market buy takes the best resting ask regardless of its own price.
+    book.submit(Order { id: 16, side: OrderSide::Sell, order_type: OrderType::Limit { price: 100.20 }, quantity: 10, sequence: 16, tif: TimeInForce::GoodTillCancel, partially_fillable: true }).unwrap();
+    let (market_trades, _market_report) = book.submit(Order {
+        id: 8,
+        side: OrderSide::Buy,
+        order_type: OrderType::Market,
+        quantity: 10,
+        sequence: 8,
+        tif: TimeInForce::ImmediateOrCancel,
+        partially_fillable: true,
+    }).unwrap();
+    println!("\nMarket Order Trades:");
+    for trade in market_trades {
+        println!(
+            "Trade: Buy Order {} <> Sell Order {}, Quantity: {}, Clearing Price: ${:.2}",
+            trade.buy_order_id,
+            trade.sell_order_id,
+            trade.quantity,
+            trade.clearing_price
+        );
+    }
+
+    // A stop-sell sits off the book until the last trade falls to its
+    // trigger price, at which point it's resubmitted as a market order.
+    book.submit(Order {
+        id: 9,
+        side: OrderSide::Sell,
+        order_type: OrderType::Stop { trigger_price: 99.75 },
+        quantity: 20,
+        sequence: 9,
+        tif: TimeInForce::GoodTillCancel,
+        partially_fillable: true,
+    }).unwrap();
+
+    // A venue with a coarser tick and lot size rejects an order that doesn't
+    // conform, returning an `OrderError` instead of panicking.
+    let strict_params = MarketParams { tick_size: 0.05, lot_size: 10, min_size: 10 };
+    let malformed_order = Order {
+        id: 10,
+        side: OrderSide::Buy,
+        order_type: OrderType::Limit { price: 99.97 },
+        quantity: 25,
+        sequence: 10,
+        tif: TimeInForce::GoodTillCancel,
+        partially_fillable: true,
+    };
+
+    let strict_engine = AuctionEngine::with_params(NBBO { bid: 99.50, ask: 100.50 }, strict_params);
+    match strict_engine.run_greedy_auction(std::slice::from_ref(&malformed_order), &[]) {
+        Ok(_) => println!("\nUnexpectedly accepted an order that violates market constraints"),
+        Err(err) => println!("\nRejected order: {:?}", err),
+    }
+
+    let mut strict_book = OrderBook::with_params(NBBO { bid: 99.50, ask: 100.50 }, strict_params);
+    match strict_book.submit(malformed_order) {
+        Ok(_) => println!("Unexpectedly accepted an order that violates market constraints"),
+        Err(err) => println!("Rejected order: {:?}", err),
+    }
+
+    // A pro-rata engine splits the clearing-price volume proportionally
+    // across oversubscribed orders instead of favoring whoever arrived first.
+    let pro_rata_engine = AuctionEngine::with_allocation_policy(
+        NBBO { bid: 90.0, ask: 110.0 },
+        MarketParams::default(),
+        AllocationPolicy::ProRata,
+    );
+    let pro_rata_buys = vec![
+        Order { id: 11, side: OrderSide::Buy, order_type: OrderType::Limit { price: 105.0 }, quantity: 40, sequence: 11, tif: TimeInForce::GoodTillCancel, partially_fillable: true },
+        Order { id: 12, side: OrderSide::Buy, order_type: OrderType::Limit { price: 105.0 }, quantity: 20, sequence: 12, tif: TimeInForce::GoodTillCancel, partially_fillable: true },
+    ];
+    let pro_rata_sells = vec![
+        Order { id: 13, side: OrderSide::Sell, order_type: OrderType::Limit { price: 95.0 }, quantity: 50, sequence: 13, tif: TimeInForce::GoodTillCancel, partially_fillable: true },
+    ];
+    let (pro_rata_trades, pro_rata_reports) = pro_rata_engine
+        .run_combinatorial_auction(&pro_rata_buys, &pro_rata_sells)
+        .expect("sample orders satisfy market constraints");
+    println!("\nCombinatorial Matched Trades (Pro-Rata Allocation):");
+    for trade in pro_rata_trades {
+        println!(
+            "Trade: Buy Order {} <> Sell Order {}, Quantity: {}, Clearing Price: ${:.2}",
+            trade.buy_order_id,
+            trade.sell_order_id,
+            trade.quantity,
+            trade.clearing_price
+        );
+    }
+    println!("\nPro-Rata Fill Reports:");
+    for report in pro_rata_reports {
+        println!(
+            "Order {}: filled {}, remaining {}, status {:?}",
+            report.order_id, report.filled_qty, report.remaining_qty, report.status
+        );
+    }
+
+    // An all-or-none order (`partially_fillable: false`) under
+    // `GoodTillCancel` is killed outright, rather than partially filled or
+    // rested, if the book can't cover it in full.
+    let mut aon_book = OrderBook::new(NBBO { bid: 99.50, ask: 100.50 });
+    aon_book.submit(Order { id: 14, side: OrderSide::Sell, order_type: OrderType::Limit { price: 99.90 }, quantity: 30, sequence: 14, tif: TimeInForce::GoodTillCancel, partially_fillable: true }).unwrap();
+    let (_, aon_report) = aon_book.submit(Order {
+        id: 15,
+        side: OrderSide::Buy,
+        order_type: OrderType::Limit { price: 99.90 },
+        quantity: 50,
+        sequence: 15,
+        tif: TimeInForce::GoodTillCancel,
+        partially_fillable: false,
+    }).unwrap();
+    println!("\nAll-Or-None Order Report: {:?}", aon_report);
 }
 
 #[cfg(test)]
@@ -234,24 +1282,24 @@ mod tests {
 
     fn sample_buy_orders() -> Vec<Order> {
         vec![
-            Order { id: 1, side: OrderSide::Buy, price: 100.00, quantity: 100 },
-            Order { id: 2, side: OrderSide::Buy, price: 99.75, quantity: 200 },
+            Order { id: 1, side: OrderSide::Buy, order_type: OrderType::Limit { price: 100.00 }, quantity: 100, sequence: 1, tif: TimeInForce::GoodTillCancel, partially_fillable: true },
+            Order { id: 2, side: OrderSide::Buy, order_type: OrderType::Limit { price: 99.75 }, quantity: 200, sequence: 2, tif: TimeInForce::GoodTillCancel, partially_fillable: true },
         ]
     }
 
     fn sample_sell_orders() -> Vec<Order> {
         vec![
-            Order { id: 3, side: OrderSide::Sell, price: 99.60, quantity: 150 },
-            Order { id: 4, side: OrderSide::Sell, price: 100.10, quantity: 100 },
+            Order { id: 3, side: OrderSide::Sell, order_type: OrderType::Limit { price: 99.60 }, quantity: 150, sequence: 3, tif: TimeInForce::GoodTillCancel, partially_fillable: true },
+            Order { id: 4, side: OrderSide::Sell, order_type: OrderType::Limit { price: 100.10 }, quantity: 100, sequence: 4, tif: TimeInForce::GoodTillCancel, partially_fillable: true },
         ]
     }
 
     #[test]
     fn test_greedy_auction_sample() {
         let engine = AuctionEngine::new(setup_nbbo());
-        let trades = engine.run_greedy_auction(&sample_buy_orders(), &sample_sell_orders());
+        let (trades, _reports) = engine.run_greedy_auction(&sample_buy_orders(), &sample_sell_orders()).unwrap();
 
-        let expected = vec![
+        let expected = [
             Trade { buy_order_id: 1, sell_order_id: 3, quantity: 100, clearing_price: 99.80 },
             Trade { buy_order_id: 2, sell_order_id: 3, quantity: 50, clearing_price: 99.675 },
         ];
@@ -268,9 +1316,9 @@ mod tests {
     #[test]
     fn test_combinatorial_auction_sample() {
         let engine = AuctionEngine::new(setup_nbbo());
-        let trades = engine.run_combinatorial_auction(&sample_buy_orders(), &sample_sell_orders());
+        let (trades, _reports) = engine.run_combinatorial_auction(&sample_buy_orders(), &sample_sell_orders()).unwrap();
 
-        let expected = vec![
+        let expected = [
             Trade { buy_order_id: 1, sell_order_id: 3, quantity: 100, clearing_price: 99.675 },
             Trade { buy_order_id: 2, sell_order_id: 3, quantity: 50, clearing_price: 99.675 },
         ];
@@ -285,41 +1333,472 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Invalid buy order ID 1: expected Buy, found Sell")]
-    fn test_invalid_buy_side_panics() {
+    fn test_invalid_buy_side_returns_error() {
         let engine = AuctionEngine::new(setup_nbbo());
-        let invalid_buy = vec![Order { id: 1, side: OrderSide::Sell, price: 100.00, quantity: 100 }];
-        engine.run_greedy_auction(&invalid_buy, &[]);
+        let invalid_buy = vec![Order { id: 1, side: OrderSide::Sell, order_type: OrderType::Limit { price: 100.00 }, quantity: 100, sequence: 1, tif: TimeInForce::GoodTillCancel, partially_fillable: true }];
+        assert_eq!(engine.run_greedy_auction(&invalid_buy, &[]), Err(OrderError::InvalidSide { order_id: 1 }));
     }
 
     #[test]
-    #[should_panic(expected = "Invalid sell order ID 3: expected Sell, found Buy")]
-    fn test_invalid_sell_side_panics() {
+    fn test_invalid_sell_side_returns_error() {
         let engine = AuctionEngine::new(setup_nbbo());
-        let invalid_sell = vec![Order { id: 3, side: OrderSide::Buy, price: 99.60, quantity: 150 }];
-        engine.run_greedy_auction(&[], &invalid_sell);
+        let invalid_sell = vec![Order { id: 3, side: OrderSide::Buy, order_type: OrderType::Limit { price: 99.60 }, quantity: 150, sequence: 3, tif: TimeInForce::GoodTillCancel, partially_fillable: true }];
+        assert_eq!(engine.run_greedy_auction(&[], &invalid_sell), Err(OrderError::InvalidSide { order_id: 3 }));
+    }
+
+    #[test]
+    fn test_price_not_on_tick_returns_error() {
+        let engine = AuctionEngine::with_params(setup_nbbo(), MarketParams { tick_size: 0.05, lot_size: 1, min_size: 1 });
+        let buy_orders = vec![Order { id: 1, side: OrderSide::Buy, order_type: OrderType::Limit { price: 99.97 }, quantity: 100, sequence: 1, tif: TimeInForce::GoodTillCancel, partially_fillable: true }];
+        assert_eq!(engine.run_greedy_auction(&buy_orders, &[]), Err(OrderError::PriceNotOnTick { order_id: 1 }));
+    }
+
+    #[test]
+    fn test_below_min_size_returns_error() {
+        let engine = AuctionEngine::with_params(setup_nbbo(), MarketParams { tick_size: 0.01, lot_size: 1, min_size: 10 });
+        let buy_orders = vec![Order { id: 1, side: OrderSide::Buy, order_type: OrderType::Limit { price: 100.00 }, quantity: 5, sequence: 1, tif: TimeInForce::GoodTillCancel, partially_fillable: true }];
+        assert_eq!(engine.run_greedy_auction(&buy_orders, &[]), Err(OrderError::BelowMinSize { order_id: 1 }));
+    }
+
+    #[test]
+    fn test_invalid_lot_size_returns_error() {
+        let engine = AuctionEngine::with_params(setup_nbbo(), MarketParams { tick_size: 0.01, lot_size: 10, min_size: 1 });
+        let buy_orders = vec![Order { id: 1, side: OrderSide::Buy, order_type: OrderType::Limit { price: 100.00 }, quantity: 25, sequence: 1, tif: TimeInForce::GoodTillCancel, partially_fillable: true }];
+        assert_eq!(engine.run_greedy_auction(&buy_orders, &[]), Err(OrderError::InvalidLotSize { order_id: 1 }));
+    }
+
+    #[test]
+    fn test_price_outside_nbbo_returns_error() {
+        let engine = AuctionEngine::new(setup_nbbo());
+        let buy_orders = vec![Order { id: 1, side: OrderSide::Buy, order_type: OrderType::Limit { price: 101.00 }, quantity: 100, sequence: 1, tif: TimeInForce::GoodTillCancel, partially_fillable: true }];
+        assert_eq!(engine.run_greedy_auction(&buy_orders, &[]), Err(OrderError::PriceOutsideNbbo { order_id: 1 }));
     }
 
     #[test]
     fn test_no_matches() {
         let engine = AuctionEngine::new(setup_nbbo());
-        let buy_orders = vec![Order { id: 1, side: OrderSide::Buy, price: 99.00, quantity: 100 }];
-        let sell_orders = vec![Order { id: 3, side: OrderSide::Sell, price: 101.00, quantity: 100 }];
-        let trades_greedy = engine.run_greedy_auction(&buy_orders, &sell_orders);
-        let trades_combo = engine.run_combinatorial_auction(&buy_orders, &sell_orders);
+        // Both orders sit within the NBBO band but don't cross each other.
+        let buy_orders = vec![Order { id: 1, side: OrderSide::Buy, order_type: OrderType::Limit { price: 99.60 }, quantity: 100, sequence: 1, tif: TimeInForce::GoodTillCancel, partially_fillable: true }];
+        let sell_orders = vec![Order { id: 3, side: OrderSide::Sell, order_type: OrderType::Limit { price: 100.40 }, quantity: 100, sequence: 3, tif: TimeInForce::GoodTillCancel, partially_fillable: true }];
+        let (trades_greedy, _greedy_reports) = engine.run_greedy_auction(&buy_orders, &sell_orders).unwrap();
+        let (trades_combo, _combo_reports) = engine.run_combinatorial_auction(&buy_orders, &sell_orders).unwrap();
         assert!(trades_greedy.is_empty());
         assert!(trades_combo.is_empty());
     }
 
     #[test]
-    fn test_zero_quantity() {
+    fn test_combinatorial_auction_pro_rata_allocation() {
+        // Two buys at the same price oversubscribe the single seller's 50
+        // units 60-to-50; pro-rata splits the 50 units proportionally
+        // (33.33 and 16.67, rounded down), then hands the one leftover unit to
+        // the order with the largest fractional remainder (order 21).
+        let engine = AuctionEngine::with_allocation_policy(
+            NBBO { bid: 90.0, ask: 110.0 },
+            MarketParams::default(),
+            AllocationPolicy::ProRata,
+        );
+        let buy_orders = vec![
+            Order { id: 20, side: OrderSide::Buy, order_type: OrderType::Limit { price: 105.0 }, quantity: 40, sequence: 1, tif: TimeInForce::GoodTillCancel, partially_fillable: true },
+            Order { id: 21, side: OrderSide::Buy, order_type: OrderType::Limit { price: 105.0 }, quantity: 20, sequence: 2, tif: TimeInForce::GoodTillCancel, partially_fillable: true },
+        ];
+        let sell_orders = vec![
+            Order { id: 22, side: OrderSide::Sell, order_type: OrderType::Limit { price: 95.0 }, quantity: 50, sequence: 3, tif: TimeInForce::GoodTillCancel, partially_fillable: true },
+        ];
+
+        let (trades, _reports) = engine.run_combinatorial_auction(&buy_orders, &sell_orders).unwrap();
+
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0].buy_order_id, 20);
+        assert_eq!(trades[0].quantity, 33);
+        assert_eq!(trades[1].buy_order_id, 21);
+        assert_eq!(trades[1].quantity, 17);
+        for trade in &trades {
+            assert!((trade.clearing_price - 100.0).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn test_combinatorial_auction_pro_rata_respects_price_priority() {
+        // buy1 bids strictly above the clearing price and buy2 sits exactly
+        // at it; only buy2 is marginal, so buy1 must fill in full (30/30)
+        // before buy2 is rationed for whatever volume is left (70 of 100).
+        let engine = AuctionEngine::with_allocation_policy(
+            NBBO { bid: 90.0, ask: 110.0 },
+            MarketParams::default(),
+            AllocationPolicy::ProRata,
+        );
+        let buy_orders = vec![
+            Order { id: 30, side: OrderSide::Buy, order_type: OrderType::Limit { price: 105.0 }, quantity: 30, sequence: 1, tif: TimeInForce::GoodTillCancel, partially_fillable: true },
+            Order { id: 31, side: OrderSide::Buy, order_type: OrderType::Limit { price: 100.0 }, quantity: 100, sequence: 2, tif: TimeInForce::GoodTillCancel, partially_fillable: true },
+        ];
+        let sell_orders = vec![
+            Order { id: 32, side: OrderSide::Sell, order_type: OrderType::Limit { price: 100.0 }, quantity: 100, sequence: 3, tif: TimeInForce::GoodTillCancel, partially_fillable: true },
+        ];
+
+        let (trades, reports) = engine.run_combinatorial_auction(&buy_orders, &sell_orders).unwrap();
+
+        let filled_qty = |order_id: u32| -> u32 {
+            trades
+                .iter()
+                .filter(|trade| trade.buy_order_id == order_id)
+                .map(|trade| trade.quantity)
+                .sum()
+        };
+        assert_eq!(filled_qty(30), 30);
+        assert_eq!(filled_qty(31), 70);
+        for trade in &trades {
+            assert!((trade.clearing_price - 100.0).abs() < 0.001);
+        }
+
+        let report_for = |order_id: u32| reports.iter().find(|r| r.order_id == order_id).unwrap();
+        assert_eq!(report_for(30).status, FillStatus::Filled);
+        assert_eq!(report_for(31).status, FillStatus::PartiallyFilled);
+    }
+
+    #[test]
+    fn test_combinatorial_auction_maximizes_volume() {
+        // Old marginal-price heuristic derived its price from the last matched
+        // pair; the volume-maximizing algorithm instead finds the price that
+        // unlocks the most executable volume (here 50 units at $97.50),
+        // independent of match order.
+        let engine = AuctionEngine::new(NBBO { bid: 90.0, ask: 110.0 });
+        let buy_orders = vec![
+            Order { id: 10, side: OrderSide::Buy, order_type: OrderType::Limit { price: 105.0 }, quantity: 40, sequence: 10, tif: TimeInForce::GoodTillCancel, partially_fillable: true },
+            Order { id: 11, side: OrderSide::Buy, order_type: OrderType::Limit { price: 100.0 }, quantity: 60, sequence: 11, tif: TimeInForce::GoodTillCancel, partially_fillable: true },
+        ];
+        let sell_orders = vec![
+            Order { id: 12, side: OrderSide::Sell, order_type: OrderType::Limit { price: 95.0 }, quantity: 50, sequence: 12, tif: TimeInForce::GoodTillCancel, partially_fillable: true },
+            Order { id: 13, side: OrderSide::Sell, order_type: OrderType::Limit { price: 102.0 }, quantity: 80, sequence: 13, tif: TimeInForce::GoodTillCancel, partially_fillable: true },
+        ];
+
+        let (trades, _reports) = engine.run_combinatorial_auction(&buy_orders, &sell_orders).unwrap();
+
+        let expected = [
+            Trade { buy_order_id: 10, sell_order_id: 12, quantity: 40, clearing_price: 97.5 },
+            Trade { buy_order_id: 11, sell_order_id: 12, quantity: 10, clearing_price: 97.5 },
+        ];
+
+        assert_eq!(trades.len(), expected.len());
+        for (actual, exp) in trades.iter().zip(expected.iter()) {
+            assert_eq!(actual.buy_order_id, exp.buy_order_id);
+            assert_eq!(actual.sell_order_id, exp.sell_order_id);
+            assert_eq!(actual.quantity, exp.quantity);
+            assert!((actual.clearing_price - exp.clearing_price).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn test_zero_quantity_below_min_size_returns_error() {
+        // With the default min_size of 1, a zero-quantity order is now a
+        // validation error rather than a silent no-op.
         let engine = AuctionEngine::new(setup_nbbo());
-        let buy_orders = vec![Order { id: 1, side: OrderSide::Buy, price: 100.00, quantity: 0 }];
-        let sell_orders = vec![Order { id: 3, side: OrderSide::Sell, price: 99.60, quantity: 0 }];
-        let trades_greedy = engine.run_greedy_auction(&buy_orders, &sell_orders);
-        let trades_combo = engine.run_combinatorial_auction(&buy_orders, &sell_orders);
-        assert!(trades_greedy.is_empty());
-        assert!(trades_combo.is_empty());
+        let buy_orders = vec![Order { id: 1, side: OrderSide::Buy, order_type: OrderType::Limit { price: 100.00 }, quantity: 0, sequence: 1, tif: TimeInForce::GoodTillCancel, partially_fillable: true }];
+        let sell_orders = vec![Order { id: 3, side: OrderSide::Sell, order_type: OrderType::Limit { price: 99.60 }, quantity: 0, sequence: 3, tif: TimeInForce::GoodTillCancel, partially_fillable: true }];
+        assert_eq!(engine.run_greedy_auction(&buy_orders, &sell_orders), Err(OrderError::BelowMinSize { order_id: 1 }));
+        assert_eq!(engine.run_combinatorial_auction(&buy_orders, &sell_orders), Err(OrderError::BelowMinSize { order_id: 1 }));
+    }
+
+    #[test]
+    fn test_greedy_auction_price_time_priority() {
+        // Two buy orders at the same price; the one with the earlier
+        // sequence number must be filled first regardless of input order.
+        let engine = AuctionEngine::new(setup_nbbo());
+        let buy_orders = vec![
+            Order { id: 1, side: OrderSide::Buy, order_type: OrderType::Limit { price: 100.00 }, quantity: 50, sequence: 2, tif: TimeInForce::GoodTillCancel, partially_fillable: true },
+            Order { id: 2, side: OrderSide::Buy, order_type: OrderType::Limit { price: 100.00 }, quantity: 50, sequence: 1, tif: TimeInForce::GoodTillCancel, partially_fillable: true },
+        ];
+        let sell_orders = vec![Order { id: 3, side: OrderSide::Sell, order_type: OrderType::Limit { price: 99.60 }, quantity: 50, sequence: 3, tif: TimeInForce::GoodTillCancel, partially_fillable: true }];
+
+        let (trades, _reports) = engine.run_greedy_auction(&buy_orders, &sell_orders).unwrap();
+
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].buy_order_id, 2);
+        assert_eq!(trades[0].sell_order_id, 3);
+        assert_eq!(trades[0].quantity, 50);
+    }
+
+    #[test]
+    fn test_order_book_price_time_priority() {
+        // Two resting bids at the same price; the earlier-sequence order
+        // must be matched first even though it was submitted second.
+        let mut book = OrderBook::new(setup_nbbo());
+        book.submit(Order { id: 1, side: OrderSide::Buy, order_type: OrderType::Limit { price: 99.80 }, quantity: 50, sequence: 2, tif: TimeInForce::GoodTillCancel, partially_fillable: true }).unwrap();
+        book.submit(Order { id: 2, side: OrderSide::Buy, order_type: OrderType::Limit { price: 99.80 }, quantity: 50, sequence: 1, tif: TimeInForce::GoodTillCancel, partially_fillable: true }).unwrap();
+
+        let (trades, _report) = book.submit(Order { id: 3, side: OrderSide::Sell, order_type: OrderType::Limit { price: 99.70 }, quantity: 50, sequence: 3, tif: TimeInForce::GoodTillCancel, partially_fillable: true }).unwrap();
+
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].buy_order_id, 2);
+    }
+
+    #[test]
+    fn test_order_book_rests_unmatched_order() {
+        let mut book = OrderBook::new(setup_nbbo());
+        let (trades, report) = book.submit(Order { id: 1, side: OrderSide::Buy, order_type: OrderType::Limit { price: 99.80 }, quantity: 100, sequence: 1, tif: TimeInForce::GoodTillCancel, partially_fillable: true }).unwrap();
+        assert!(trades.is_empty());
+        assert_eq!(report.status, FillStatus::Unfilled);
+        assert_eq!(book.remaining.get(&1), Some(&100));
+    }
+
+    #[test]
+    fn test_order_book_matches_against_resting_order() {
+        let mut book = OrderBook::new(setup_nbbo());
+        book.submit(Order { id: 1, side: OrderSide::Buy, order_type: OrderType::Limit { price: 99.80 }, quantity: 100, sequence: 1, tif: TimeInForce::GoodTillCancel, partially_fillable: true }).unwrap();
+        let (trades, report) = book.submit(Order { id: 2, side: OrderSide::Sell, order_type: OrderType::Limit { price: 99.70 }, quantity: 40, sequence: 2, tif: TimeInForce::GoodTillCancel, partially_fillable: true }).unwrap();
+
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].buy_order_id, 1);
+        assert_eq!(trades[0].sell_order_id, 2);
+        assert_eq!(trades[0].quantity, 40);
+        assert!((trades[0].clearing_price - 99.80).abs() < 0.001);
+        // The resting buy order has 60 units left on the book.
+        assert_eq!(book.remaining.get(&1), Some(&60));
+        assert_eq!(book.remaining.get(&2), None);
+        assert_eq!(report, OrderFillReport { order_id: 2, filled_qty: 40, remaining_qty: 0, status: FillStatus::Filled });
+    }
+
+    #[test]
+    fn test_order_book_cancel_removes_resting_order() {
+        let mut book = OrderBook::new(setup_nbbo());
+        book.submit(Order { id: 1, side: OrderSide::Buy, order_type: OrderType::Limit { price: 99.80 }, quantity: 100, sequence: 1, tif: TimeInForce::GoodTillCancel, partially_fillable: true }).unwrap();
+
+        assert!(book.cancel(1));
+        assert!(!book.cancel(1));
+
+        let (trades, _report) = book.submit(Order { id: 2, side: OrderSide::Sell, order_type: OrderType::Limit { price: 99.70 }, quantity: 40, sequence: 2, tif: TimeInForce::GoodTillCancel, partially_fillable: true }).unwrap();
+        assert!(trades.is_empty());
+    }
+
+    #[test]
+    fn test_order_book_amend_reduces_resting_quantity() {
+        let mut book = OrderBook::new(setup_nbbo());
+        book.submit(Order { id: 1, side: OrderSide::Buy, order_type: OrderType::Limit { price: 99.80 }, quantity: 100, sequence: 1, tif: TimeInForce::GoodTillCancel, partially_fillable: true }).unwrap();
+
+        assert!(book.amend(1, 30));
+        assert!(!book.amend(1, 50)); // not a reduction
+
+        let (trades, _report) = book.submit(Order { id: 2, side: OrderSide::Sell, order_type: OrderType::Limit { price: 99.70 }, quantity: 100, sequence: 2, tif: TimeInForce::GoodTillCancel, partially_fillable: true }).unwrap();
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].quantity, 30);
+        // The remaining 70 units of the incoming sell rest on the book.
+        assert_eq!(book.remaining.get(&2), Some(&70));
+    }
+
+    #[test]
+    fn test_order_book_respects_nbbo_band() {
+        let mut book = OrderBook::new(setup_nbbo());
+        // A sell priced below the NBBO bid is rejected outright rather than
+        // silently resting or failing to trade.
+        let result = book.submit(Order { id: 1, side: OrderSide::Sell, order_type: OrderType::Limit { price: 99.00 }, quantity: 100, sequence: 1, tif: TimeInForce::GoodTillCancel, partially_fillable: true });
+        assert_eq!(result, Err(OrderError::PriceOutsideNbbo { order_id: 1 }));
+        assert_eq!(book.remaining.get(&1), None);
+    }
+
+    #[test]
+    fn test_order_book_market_order_takes_best_ask() {
+        let mut book = OrderBook::new(setup_nbbo());
+        book.submit(Order { id: 1, side: OrderSide::Sell, order_type: OrderType::Limit { price: 99.90 }, quantity: 50, sequence: 1, tif: TimeInForce::GoodTillCancel, partially_fillable: true }).unwrap();
+
+        let (trades, _report) = book.submit(Order {
+            id: 2,
+            side: OrderSide::Buy,
+            order_type: OrderType::Market,
+            quantity: 30,
+            sequence: 2,
+            tif: TimeInForce::ImmediateOrCancel,
+            partially_fillable: true,
+        }).unwrap();
+
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].sell_order_id, 1);
+        assert_eq!(trades[0].quantity, 30);
+        assert!((trades[0].clearing_price - 99.90).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_order_book_immediate_or_cancel_drops_unfilled_remainder() {
+        let mut book = OrderBook::new(setup_nbbo());
+        book.submit(Order { id: 1, side: OrderSide::Sell, order_type: OrderType::Limit { price: 99.90 }, quantity: 10, sequence: 1, tif: TimeInForce::GoodTillCancel, partially_fillable: true }).unwrap();
+
+        let (trades, _report) = book.submit(Order {
+            id: 2,
+            side: OrderSide::Buy,
+            order_type: OrderType::Limit { price: 99.90 },
+            quantity: 30,
+            sequence: 2,
+            tif: TimeInForce::ImmediateOrCancel,
+            partially_fillable: true,
+        }).unwrap();
+
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].quantity, 10);
+        // The unfilled 20 units do not rest on the book.
+        assert_eq!(book.remaining.get(&2), None);
+    }
+
+    #[test]
+    fn test_order_book_fill_or_kill_all_or_nothing() {
+        let mut book = OrderBook::new(setup_nbbo());
+        book.submit(Order { id: 1, side: OrderSide::Sell, order_type: OrderType::Limit { price: 99.90 }, quantity: 10, sequence: 1, tif: TimeInForce::GoodTillCancel, partially_fillable: true }).unwrap();
+
+        // Cannot be filled in full (only 10 of 30 units available) -> killed.
+        let (killed, killed_report) = book.submit(Order {
+            id: 2,
+            side: OrderSide::Buy,
+            order_type: OrderType::Limit { price: 99.90 },
+            quantity: 30,
+            sequence: 2,
+            tif: TimeInForce::FillOrKill,
+            partially_fillable: true,
+        }).unwrap();
+        assert!(killed.is_empty());
+        assert_eq!(killed_report.status, FillStatus::Killed);
+        // The resting sell order is untouched.
+        assert_eq!(book.remaining.get(&1), Some(&10));
+
+        // Can be filled in full -> trades normally.
+        let (filled, filled_report) = book.submit(Order {
+            id: 3,
+            side: OrderSide::Buy,
+            order_type: OrderType::Limit { price: 99.90 },
+            quantity: 10,
+            sequence: 3,
+            tif: TimeInForce::FillOrKill,
+            partially_fillable: true,
+        }).unwrap();
+        assert_eq!(filled.len(), 1);
+        assert_eq!(filled[0].quantity, 10);
+        assert_eq!(filled_report.status, FillStatus::Filled);
+    }
+
+    #[test]
+    fn test_order_book_stop_order_triggers_as_market_order() {
+        let mut book = OrderBook::new(setup_nbbo());
+        // A stop-sell that waits for the last trade to fall to $99.70 or below.
+        let (resting, resting_report) = book.submit(Order {
+            id: 1,
+            side: OrderSide::Sell,
+            order_type: OrderType::Stop { trigger_price: 99.70 },
+            quantity: 20,
+            sequence: 1,
+            tif: TimeInForce::GoodTillCancel,
+            partially_fillable: true,
+        }).unwrap();
+        assert!(resting.is_empty());
+        assert_eq!(resting_report.status, FillStatus::Unfilled);
+        assert_eq!(book.pending_stops.len(), 1);
+
+        // A resting bid at $99.60, then a trade prints at that price, crossing the trigger.
+        book.submit(Order { id: 2, side: OrderSide::Buy, order_type: OrderType::Limit { price: 99.60 }, quantity: 5, sequence: 2, tif: TimeInForce::GoodTillCancel, partially_fillable: true }).unwrap();
+        let (trades, _report) = book.submit(Order { id: 3, side: OrderSide::Sell, order_type: OrderType::Limit { price: 99.55 }, quantity: 5, sequence: 3, tif: TimeInForce::GoodTillCancel, partially_fillable: true }).unwrap();
+        assert_eq!(trades.len(), 1);
+        assert!((trades[0].clearing_price - 99.60).abs() < 0.001);
+
+        // The stop order should have triggered and converted to a market
+        // order; with no bids left to trade against it finds no fill and,
+        // being a market order, does not rest on the book either.
+        assert!(book.pending_stops.is_empty());
+        assert_eq!(book.remaining.get(&1), None);
+    }
+
+    #[test]
+    fn test_greedy_auction_fill_or_kill_removes_partially_filled_order() {
+        // A fill-or-kill buy that can only be partially filled gets none of
+        // its trades; the unconstrained buy order, which has price priority
+        // and is matched first, still fills normally.
+        let engine = AuctionEngine::new(setup_nbbo());
+        let buy_orders = vec![
+            Order { id: 1, side: OrderSide::Buy, order_type: OrderType::Limit { price: 99.75 }, quantity: 200, sequence: 1, tif: TimeInForce::FillOrKill, partially_fillable: true },
+            Order { id: 2, side: OrderSide::Buy, order_type: OrderType::Limit { price: 100.00 }, quantity: 50, sequence: 2, tif: TimeInForce::GoodTillCancel, partially_fillable: true },
+        ];
+        let sell_orders = vec![
+            Order { id: 3, side: OrderSide::Sell, order_type: OrderType::Limit { price: 99.60 }, quantity: 150, sequence: 3, tif: TimeInForce::GoodTillCancel, partially_fillable: true },
+        ];
+
+        let (trades, reports) = engine.run_greedy_auction(&buy_orders, &sell_orders).unwrap();
+
+        assert!(trades.iter().all(|t| t.buy_order_id != 1));
+        assert!(trades.iter().any(|t| t.buy_order_id == 2));
+        let report_1 = reports.iter().find(|r| r.order_id == 1).unwrap();
+        assert_eq!(report_1.status, FillStatus::Killed);
+        let report_2 = reports.iter().find(|r| r.order_id == 2).unwrap();
+        assert_eq!(report_2.status, FillStatus::Filled);
     }
 
+    #[test]
+    fn test_greedy_auction_killed_fill_or_kill_order_does_not_starve_others() {
+        // buy1 (FillOrKill, better price, matched first) can only take 10 of
+        // its 30 units from the sole seller and gets killed; that must not
+        // consume the seller's liquidity that buy2 (GoodTillCancel) needed.
+        let engine = AuctionEngine::new(setup_nbbo());
+        let buy_orders = vec![
+            Order { id: 1, side: OrderSide::Buy, order_type: OrderType::Limit { price: 99.90 }, quantity: 30, sequence: 1, tif: TimeInForce::FillOrKill, partially_fillable: true },
+            Order { id: 2, side: OrderSide::Buy, order_type: OrderType::Limit { price: 99.80 }, quantity: 10, sequence: 2, tif: TimeInForce::GoodTillCancel, partially_fillable: true },
+        ];
+        let sell_orders = vec![
+            Order { id: 3, side: OrderSide::Sell, order_type: OrderType::Limit { price: 99.60 }, quantity: 10, sequence: 3, tif: TimeInForce::GoodTillCancel, partially_fillable: true },
+        ];
+
+        let (trades, reports) = engine.run_greedy_auction(&buy_orders, &sell_orders).unwrap();
+
+        assert!(trades.iter().all(|t| t.buy_order_id != 1));
+        assert!(trades.iter().any(|t| t.buy_order_id == 2 && t.quantity == 10));
+        let report_1 = reports.iter().find(|r| r.order_id == 1).unwrap();
+        assert_eq!(report_1.status, FillStatus::Killed);
+        let report_2 = reports.iter().find(|r| r.order_id == 2).unwrap();
+        assert_eq!(report_2.status, FillStatus::Filled);
+    }
+
+    #[test]
+    fn test_cascading_fok_kill_should_not_starve_rescuable_order() {
+        // buy1 (FillOrKill, better price, matched first) consumes the sole
+        // seller's entire 10 units but needs 15 in all, so it's unfillable and
+        // gets killed. buy2 (FillOrKill, lower priority) looks unfillable in
+        // that same snapshot too, since buy1 left nothing behind — but once
+        // buy1 is excluded, the seller's 10 units are exactly what buy2 needs.
+        let engine = AuctionEngine::new(setup_nbbo());
+        let buy_orders = vec![
+            Order { id: 1, side: OrderSide::Buy, order_type: OrderType::Limit { price: 100.00 }, quantity: 15, sequence: 1, tif: TimeInForce::FillOrKill, partially_fillable: true },
+            Order { id: 2, side: OrderSide::Buy, order_type: OrderType::Limit { price: 99.90 }, quantity: 10, sequence: 2, tif: TimeInForce::FillOrKill, partially_fillable: true },
+        ];
+        let sell_orders = vec![
+            Order { id: 100, side: OrderSide::Sell, order_type: OrderType::Limit { price: 99.60 }, quantity: 10, sequence: 3, tif: TimeInForce::GoodTillCancel, partially_fillable: true },
+        ];
+
+        let (trades, reports) = engine.run_greedy_auction(&buy_orders, &sell_orders).unwrap();
+
+        assert!(trades.iter().all(|t| t.buy_order_id != 1));
+        assert!(trades.iter().any(|t| t.buy_order_id == 2 && t.quantity == 10));
+        let report_1 = reports.iter().find(|r| r.order_id == 1).unwrap();
+        assert_eq!(report_1.status, FillStatus::Killed);
+        let report_2 = reports.iter().find(|r| r.order_id == 2).unwrap();
+        assert_eq!(report_2.status, FillStatus::Filled);
+    }
+
+    #[test]
+    fn test_order_book_all_or_none_order_killed_without_tif_fill_or_kill() {
+        // `partially_fillable: false` enforces all-or-none even under
+        // `GoodTillCancel`, independent of `TimeInForce::FillOrKill`.
+        let mut book = OrderBook::new(setup_nbbo());
+        book.submit(Order { id: 1, side: OrderSide::Sell, order_type: OrderType::Limit { price: 99.90 }, quantity: 10, sequence: 1, tif: TimeInForce::GoodTillCancel, partially_fillable: true }).unwrap();
+
+        let (trades, report) = book.submit(Order {
+            id: 2,
+            side: OrderSide::Buy,
+            order_type: OrderType::Limit { price: 99.90 },
+            quantity: 30,
+            sequence: 2,
+            tif: TimeInForce::GoodTillCancel,
+            partially_fillable: false,
+        }).unwrap();
+
+        assert!(trades.is_empty());
+        assert_eq!(report.status, FillStatus::Killed);
+        assert_eq!(report.filled_qty, 0);
+        assert_eq!(report.remaining_qty, 30);
+        // The killed order does not rest on the book either.
+        assert_eq!(book.remaining.get(&2), None);
+        // The resting sell order is untouched.
+        assert_eq!(book.remaining.get(&1), Some(&10));
+    }
 }
\ No newline at end of file